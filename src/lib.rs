@@ -5,11 +5,11 @@ use std::{
     collections::BTreeMap,
     error::Error,
     fs::File,
-    io::Write,
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 use structopt::StructOpt;
-use toml_edit::{Array, Decor, Document, InlineTable, Item, Table, Value};
+use toml_edit::{Array, ArrayOfTables, Decor, Document, InlineTable, Item, Table, Value};
 
 /// Type alias for shorter return types.
 pub type Res<T> = Result<T, Box<dyn Error>>;
@@ -31,9 +31,24 @@ pub struct Opt {
     /// If not provide the files will be overritten.
     #[structopt(short, long)]
     pub check: bool,
+
+    /// When the formatting differs, print a unified diff to stderr.
+    /// Has no effect outside of `--check`.
+    #[structopt(short, long)]
+    pub diff: bool,
+
+    /// Read the document from stdin and write the result to stdout instead
+    /// of reading/writing files. Implied by passing `-` as the only file.
+    #[structopt(long)]
+    pub stdin: bool,
+
+    /// Apply a built-in canonical ordering profile on top of `keys`/`inline_keys`.
+    /// Currently only `cargo` is supported, overrides the `profile` config key.
+    #[structopt(long)]
+    pub profile: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     /// Important keys in non-inline tables.
     /// Will be sorted first, then any non-important keys will be
@@ -47,11 +62,87 @@ pub struct Config {
     #[serde(default)]
     pub inline_keys: Vec<String>,
 
-    /// Does it sort arrays of strings ?
-    /// In case of mixed types, string will be ordered first, then
+    /// Does it sort homogeneous arrays of strings, integers, floats or
+    /// booleans ?
+    /// In case of mixed types, strings will be ordered first, then
     /// other values in original order.
     #[serde(default)]
+    pub sort_arrays: bool,
+
+    /// Deprecated alias for the old string-only behavior: sorts `Value::String`
+    /// elements only, leaving every other type in original order, exactly as
+    /// before `sort_arrays` existed. Superseded by `sort_arrays`, which also
+    /// sorts integers, floats and booleans; kept so existing configs keep
+    /// their documented, string-only behavior unchanged.
+    #[serde(default)]
     pub sort_string_arrays: bool,
+
+    /// Sort arrays in descending order instead of ascending. Has no effect
+    /// unless `sort_arrays` is set.
+    #[serde(default)]
+    pub arrays_descending: bool,
+
+    /// If set, arrays of tables (`[[name]]` blocks) are sorted by the
+    /// string value of this key in each table. Tables missing the key
+    /// keep their relative order and are pushed to the end.
+    /// When unset, arrays of tables keep their original relative order.
+    #[serde(default)]
+    pub sort_array_of_tables_by: Option<String>,
+
+    /// Maximum number of consecutive blank lines to keep between entries.
+    /// Any longer run found in the input is collapsed down to this limit.
+    #[serde(default = "default_max_blank_lines")]
+    pub max_blank_lines: usize,
+
+    /// Ensure exactly one blank line separates top-level non-inline tables,
+    /// even if the input had none.
+    #[serde(default)]
+    pub blank_line_between_tables: bool,
+
+    /// Indentation style for multi-line arrays and nested structures:
+    /// `"tab"` or `"space"`.
+    #[serde(default = "default_indent_style")]
+    pub indent_style: String,
+
+    /// Number of spaces per indentation level when `indent_style = "space"`.
+    /// Ignored when `indent_style = "tab"`.
+    #[serde(default = "default_indent_size")]
+    pub indent_size: usize,
+
+    /// Built-in canonical ordering profile to layer on top of `keys`/`inline_keys`.
+    /// Currently only `"cargo"` is recognized; anything else is ignored.
+    #[serde(default)]
+    pub profile: String,
+}
+
+fn default_max_blank_lines() -> usize {
+    1
+}
+
+fn default_indent_style() -> String {
+    "tab".to_string()
+}
+
+fn default_indent_size() -> usize {
+    2
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            keys: Vec::new(),
+            inline_keys: Vec::new(),
+            sort_arrays: false,
+            sort_string_arrays: false,
+            arrays_descending: false,
+            sort_array_of_tables_by: None,
+            max_blank_lines: default_max_blank_lines(),
+            blank_line_between_tables: false,
+            indent_style: default_indent_style(),
+            indent_size: default_indent_size(),
+            profile: String::new(),
+        }
+    }
 }
 
 const CONFIG_FILE: &'static str = "toml-sort.toml";
@@ -89,18 +180,56 @@ pub struct ProcessedConfig {
     /// sorted lexicographically.
     pub inline_keys: BTreeMap<String, usize>,
 
-    /// Does it sort arrays of strings ?
-    /// In case of mixed types, string will be ordered first, then
+    /// Does it sort homogeneous arrays of strings, integers, floats or
+    /// booleans ?
+    /// In case of mixed types, strings will be ordered first, then
     /// other values in original order.
+    pub sort_arrays: bool,
+
+    /// Deprecated: sorts `Value::String` elements only, leaving every other
+    /// type in original order. Superseded by `sort_arrays`.
     pub sort_string_arrays: bool,
+
+    /// Sort arrays in descending order instead of ascending.
+    pub arrays_descending: bool,
+
+    /// If set, arrays of tables (`[[name]]` blocks) are sorted by the
+    /// string value of this key in each table.
+    pub sort_array_of_tables_by: Option<String>,
+
+    /// Maximum number of consecutive blank lines to keep between entries.
+    pub max_blank_lines: usize,
+
+    /// Ensure exactly one blank line separates top-level non-inline tables.
+    pub blank_line_between_tables: bool,
+
+    /// Resolved per-level indentation string (e.g. `"\t"` or `"  "`), applied
+    /// a number of times matching the current nesting depth.
+    pub indent_unit: String,
+
+    /// Built-in canonical ordering profile, e.g. `"cargo"`.
+    pub profile: String,
 }
 
 impl From<Config> for ProcessedConfig {
     fn from(x: Config) -> Self {
+        let indent_unit = if x.indent_style == "space" {
+            " ".repeat(x.indent_size)
+        } else {
+            "\t".to_string()
+        };
+
         let mut res = Self {
             keys: BTreeMap::new(),
             inline_keys: BTreeMap::new(),
+            sort_arrays: x.sort_arrays,
             sort_string_arrays: x.sort_string_arrays,
+            arrays_descending: x.arrays_descending,
+            sort_array_of_tables_by: x.sort_array_of_tables_by,
+            max_blank_lines: x.max_blank_lines,
+            blank_line_between_tables: x.blank_line_between_tables,
+            indent_unit,
+            profile: x.profile,
         };
 
         for (i, key) in x.keys.iter().enumerate() {
@@ -119,9 +248,306 @@ fn absolute_path(path: impl AsRef<Path>) -> Res<String> {
     Ok(std::fs::canonicalize(&path)?.to_string_lossy().to_string())
 }
 
+/// Read the string value of `key` in `table`, if present.
+fn identity_value(table: &Table, key: &str) -> Option<String> {
+    table
+        .get(key)
+        .and_then(Item::as_value)
+        .and_then(Value::as_str)
+        .map(|s| s.to_string())
+}
+
+/// Append `key` to the dotted table `path` (e.g. `join_path("target.cfg", "dependencies")`).
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Canonical key ordering for a Cargo dependency spec, used both for inline
+/// dependency tables (`serde = { version = "1", ... }`) and for non-inline
+/// dependency sub-tables (`[dependencies.serde]`).
+const CARGO_DEPENDENCY_KEY_ORDER: &[&str] = &[
+    "version",
+    "path",
+    "git",
+    "branch",
+    "tag",
+    "rev",
+    "registry",
+    "package",
+    "features",
+    "default-features",
+    "optional",
+];
+
+/// Is `path` a `[dependencies]` / `[dev-dependencies]` / `[build-dependencies]`
+/// table, at the root or nested under e.g. `[target.'cfg(...)']`?
+fn is_cargo_dependency_table(path: &str) -> bool {
+    path == "dependencies"
+        || path == "dev-dependencies"
+        || path == "build-dependencies"
+        || path.ends_with(".dependencies")
+        || path.ends_with(".dev-dependencies")
+        || path.ends_with(".build-dependencies")
+}
+
+/// Canonical key ordering for `profile = "cargo"`, keyed by the dotted path
+/// of the enclosing table. Mirrors the conventions used by rustfmt's
+/// Cargo.toml formatter.
+fn cargo_profile_keys(path: &str) -> Option<&'static [&'static str]> {
+    match path {
+        "" => Some(&[
+            "package",
+            "workspace",
+            "lib",
+            "bin",
+            "example",
+            "test",
+            "bench",
+            "features",
+            "dependencies",
+            "dev-dependencies",
+            "build-dependencies",
+            "target",
+            "badges",
+            "profile",
+            "patch",
+            "replace",
+        ]),
+        "package" => Some(&[
+            "name",
+            "version",
+            "authors",
+            "edition",
+            "rust-version",
+            "description",
+            "documentation",
+            "readme",
+            "homepage",
+            "repository",
+            "license",
+            "license-file",
+            "keywords",
+            "categories",
+            "workspace",
+            "build",
+            "links",
+            "exclude",
+            "include",
+            "publish",
+            "metadata",
+        ]),
+        // A non-inline dependency sub-table, e.g. `[dependencies.serde]`: the
+        // parent path names a dependency table and `path`'s last segment is
+        // the dependency name.
+        _ if join_path_parent(path).map_or(false, is_cargo_dependency_table) => {
+            Some(CARGO_DEPENDENCY_KEY_ORDER)
+        }
+        _ => None,
+    }
+}
+
+/// The parent of a dotted table `path` (e.g. `"dependencies"` for
+/// `"dependencies.serde"`), or `None` for a root-level path.
+fn join_path_parent(path: &str) -> Option<&str> {
+    path.rsplit_once('.').map(|(parent, _)| parent)
+}
+
+/// Canonical inline-table key ordering for `profile = "cargo"`, keyed by the
+/// dotted path of the table holding the dependency entries (e.g. `"dependencies"`).
+fn cargo_profile_inline_keys(path: &str) -> Option<&'static [&'static str]> {
+    if is_cargo_dependency_table(path) {
+        Some(CARGO_DEPENDENCY_KEY_ORDER)
+    } else {
+        None
+    }
+}
+
+/// Collapse runs of consecutive newlines so that at most `max_blank_lines`
+/// blank lines remain, leaving shorter runs untouched. A run at the start of
+/// `input` is capped at `max_blank_lines` newlines (the preceding line's
+/// terminator is not part of a decor prefix); a run after other content, such
+/// as a comment line, also holds that line's own terminator and is capped at
+/// `max_blank_lines + 1`.
+fn collapse_blank_lines(input: &str, max_blank_lines: usize) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut newline_run = 0usize;
+    let mut leading = true;
+
+    for c in input.chars() {
+        if c == '\n' {
+            newline_run += 1;
+            let cap = if leading { max_blank_lines } else { max_blank_lines + 1 };
+            if newline_run <= cap {
+                result.push(c);
+            }
+        } else {
+            newline_run = 0;
+            leading = false;
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Make `prefix` start with exactly one blank line (one newline, since the
+/// line terminator itself is implicit and not part of the prefix), whatever
+/// number of blank lines the input had.
+fn ensure_blank_line_prefix(prefix: &str) -> String {
+    format!("\n{}", prefix.trim_start_matches('\n'))
+}
+
+/// A single line-level diff operation, as produced by [`diff_lines`].
+enum DiffOp<'a> {
+    Equal(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Compute a line-level LCS diff between `old` and `new`, returning the
+/// sequence of operations needed to turn `old` into `new`.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffOp<'a>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let n = old_lines.len();
+    let m = new_lines.len();
+
+    // `dp[i][j]` is the length of the LCS of `old_lines[i..]` and `new_lines[j..]`.
+    let mut dp = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            dp[i][j] = if old_lines[i] == new_lines[j] {
+                dp[i + 1][j + 1] + 1
+            } else {
+                dp[i + 1][j].max(dp[i][j + 1])
+            };
+        }
+    }
+
+    // Backtrack from (0, 0) to emit operations in order.
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            ops.push(DiffOp::Equal(old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if dp[i + 1][j] >= dp[i][j + 1] {
+            ops.push(DiffOp::Removed(old_lines[i]));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Added(new_lines[j]));
+            j += 1;
+        }
+    }
+    while i < n {
+        ops.push(DiffOp::Removed(old_lines[i]));
+        i += 1;
+    }
+    while j < m {
+        ops.push(DiffOp::Added(new_lines[j]));
+        j += 1;
+    }
+
+    ops
+}
+
+/// Number of context lines kept around a change when grouping a diff into hunks.
+const DIFF_CONTEXT: usize = 3;
+
+/// Render a colored unified diff of `old` vs `new`, grouping changes into
+/// hunks with a few lines of surrounding context.
+fn unified_diff(old: &str, new: &str) -> String {
+    let ops = diff_lines(old, new);
+
+    // Track 1-based old/new line numbers alongside each op.
+    let mut numbered = Vec::with_capacity(ops.len());
+    let (mut old_line, mut new_line) = (1usize, 1usize);
+    for op in &ops {
+        match op {
+            DiffOp::Equal(line) => {
+                numbered.push((old_line, new_line, *line, op));
+                old_line += 1;
+                new_line += 1;
+            }
+            DiffOp::Removed(line) => {
+                numbered.push((old_line, new_line, *line, op));
+                old_line += 1;
+            }
+            DiffOp::Added(line) => {
+                numbered.push((old_line, new_line, *line, op));
+                new_line += 1;
+            }
+        }
+    }
+
+    // Group changed lines (plus surrounding context) into hunks.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (idx, (_, _, _, op)) in numbered.iter().enumerate() {
+        if matches!(op, DiffOp::Equal(_)) {
+            continue;
+        }
+        let start = idx.saturating_sub(DIFF_CONTEXT);
+        let end = (idx + DIFF_CONTEXT + 1).min(numbered.len());
+
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = String::new();
+    for (start, end) in hunks {
+        let old_start = numbered[start].0;
+        let new_start = numbered[start].1;
+        let old_count = numbered[start..end]
+            .iter()
+            .filter(|(_, _, _, op)| !matches!(op, DiffOp::Added(_)))
+            .count();
+        let new_count = numbered[start..end]
+            .iter()
+            .filter(|(_, _, _, op)| !matches!(op, DiffOp::Removed(_)))
+            .count();
+
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+
+        for (_, _, line, op) in &numbered[start..end] {
+            match op {
+                DiffOp::Equal(_) => output.push_str(&format!("  {}\n", line)),
+                DiffOp::Removed(_) => output.push_str(&format!("{}\n", format!("- {}", line).red())),
+                DiffOp::Added(_) => {
+                    output.push_str(&format!("{}\n", format!("+ {}", line).green()))
+                }
+            }
+        }
+    }
+
+    output.trim_end().to_string()
+}
+
 impl ProcessedConfig {
+    /// Format a TOML document held in memory, without touching the filesystem.
+    /// Shared by the file and stdin processing paths.
+    pub fn format_str(&self, input: &str) -> Res<String> {
+        let doc = input.parse::<Document>()?;
+        let trailing = doc.trailing().trim_end();
+
+        let output_table = self.format_table(&doc, "")?;
+        let mut output_doc: Document = output_table.into();
+        output_doc.set_trailing(trailing); // Insert back trailing content (comments).
+
+        Ok(format!("{}\n", output_doc.to_string().trim()))
+    }
+
     /// Process the provided file.
-    pub fn process_file(&self, path: impl AsRef<Path>, check: bool) -> Res<()> {
+    pub fn process_file(&self, path: impl AsRef<Path>, check: bool, diff: bool) -> Res<()> {
         let absolute_path = absolute_path(&path)?;
         let text = std::fs::read_to_string(&path).unwrap_or_else(|e| {
             eprintln!(
@@ -132,17 +558,14 @@ impl ProcessedConfig {
             std::process::exit(3);
         });
 
-        let doc = text.parse::<Document>()?;
-        let trailing = doc.trailing().trim_end();
-
-        let output_table = self.format_table(&doc)?;
-        let mut output_doc: Document = output_table.into();
-        output_doc.set_trailing(trailing); // Insert back trailing content (comments).
-        let output_text = format!("{}\n", output_doc.to_string().trim());
+        let output_text = self.format_str(&text)?;
 
         if check {
             if text != output_text {
                 eprintln!("Check fails : {}", absolute_path.red());
+                if diff {
+                    eprintln!("{}", unified_diff(&text, &output_text));
+                }
                 std::process::exit(2);
             } else {
                 println!("Check succeed: {}", absolute_path.green());
@@ -161,24 +584,55 @@ impl ProcessedConfig {
         Ok(())
     }
 
+    /// Read a TOML document from stdin, format it, and write the result to
+    /// stdout. In `--check` mode nothing is written to stdout; only the exit
+    /// code reflects whether the input was already formatted.
+    pub fn process_stdin(&self, check: bool, diff: bool) -> Res<()> {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+
+        let output_text = self.format_str(&text)?;
+
+        if check {
+            if text != output_text {
+                if diff {
+                    eprintln!("{}", unified_diff(&text, &output_text));
+                }
+                std::process::exit(2);
+            }
+        } else {
+            let stdout = std::io::stdout();
+            let mut stdout = stdout.lock();
+            stdout.write_all(output_text.as_bytes())?;
+            stdout.flush()?;
+        }
+
+        Ok(())
+    }
+
     /// Format a `Table`.
     /// Consider empty lines as "sections" and will not sort accross sections.
     /// Comments at the start of the section will stay at the start, while
     /// comments attached to any other line will stay attached to that line.
-    fn format_table(&self, table: &Table) -> Res<Table> {
+    fn format_table(&self, table: &Table, path: &str) -> Res<Table> {
+        let keys = self.effective_keys(path);
         let mut formated_table = Table::new();
         formated_table.set_implicit(true); // avoid empty `[dotted.keys]`
         let prefix = table.decor().prefix().unwrap_or("");
         let suffix = table.decor().suffix().unwrap_or("");
-        formated_table.decor_mut().set_prefix(prefix);
-        formated_table.decor_mut().set_suffix(suffix);
+        formated_table
+            .decor_mut()
+            .set_prefix(collapse_blank_lines(prefix, self.max_blank_lines));
+        formated_table
+            .decor_mut()
+            .set_suffix(collapse_blank_lines(suffix, self.max_blank_lines));
 
         let mut section_decor = Decor::default();
         let mut section = Vec::<Entry<Item>>::new();
 
         let sort = |x: &Entry<Item>, y: &Entry<Item>| {
-            let xord = self.keys.get(&x.key);
-            let yord = self.keys.get(&y.key);
+            let xord = keys.get(&x.key);
+            let yord = keys.get(&y.key);
 
             match (xord, yord) {
                 (Some(_), None) => Ordering::Less,
@@ -192,6 +646,11 @@ impl ProcessedConfig {
         for (i, (key, item)) in table.iter().enumerate() {
             let mut key_decor = table.key_decor(key).unwrap().clone();
 
+            // Collapse long runs of blank lines down to the configured limit.
+            if let Some(prefix) = key_decor.prefix().map(|x| x.to_owned()) {
+                key_decor.set_prefix(collapse_blank_lines(&prefix, self.max_blank_lines));
+            }
+
             // First entry can be decored (prefix).
             // In that case we want to keep that decoration at the start of the section.
             if i == 0 {
@@ -236,12 +695,14 @@ impl ProcessedConfig {
             }
 
             // Format inner item.
+            let child_path = join_path(path, key);
             let new_item = match item {
                 Item::None => Item::None,
-                Item::Value(inner) => Item::Value(self.format_value(&inner, false)?),
-                Item::Table(inner) => Item::Table(self.format_table(inner)?),
-                // TODO : Doesn't seem we have any of those.
-                Item::ArrayOfTables(inner) => Item::ArrayOfTables(inner.clone()),
+                Item::Value(inner) => Item::Value(self.format_value(&inner, false, 0, path)?),
+                Item::Table(inner) => Item::Table(self.format_table(inner, &child_path)?),
+                Item::ArrayOfTables(inner) => {
+                    Item::ArrayOfTables(self.format_array_of_tables(inner, &child_path)?)
+                }
             };
 
             section.push(Entry {
@@ -266,13 +727,73 @@ impl ProcessedConfig {
             *formated_table.key_decor_mut(&entry.key).unwrap() = entry.decor;
         }
 
+        // Ensure exactly one blank line separates top-level non-inline tables,
+        // `[[name]]` blocks included, from whatever precedes them.
+        if self.blank_line_between_tables && path.is_empty() {
+            let keys: Vec<String> = formated_table.iter().map(|(k, _)| k.to_string()).collect();
+
+            for (i, key) in keys.iter().enumerate() {
+                let mut tables: Vec<&mut Table> = match formated_table.get_mut(key) {
+                    Some(Item::Table(table)) => vec![table],
+                    Some(Item::ArrayOfTables(array)) => array.iter_mut().collect(),
+                    _ => Vec::new(),
+                };
+
+                for (j, table) in tables.iter_mut().enumerate() {
+                    if i == 0 && j == 0 {
+                        continue;
+                    }
+
+                    let decor = table.decor_mut();
+                    let prefix = decor.prefix().unwrap_or("").to_string();
+                    decor.set_prefix(ensure_blank_line_prefix(&prefix));
+                }
+            }
+        }
+
         Ok(formated_table)
     }
 
+    /// Format an `ArrayOfTables` (`[[name]]` blocks).
+    /// Each inner `Table` is formatted (and internally key-sorted) just like a
+    /// standalone table. The relative order of the array elements themselves
+    /// is preserved, unless `sort_array_of_tables_by` names an identity key
+    /// present in the tables, in which case elements are sorted by that
+    /// key's string value, with tables missing the key pushed to the end.
+    fn format_array_of_tables(&self, array: &ArrayOfTables, path: &str) -> Res<ArrayOfTables> {
+        let mut tables = array
+            .iter()
+            .map(|table| self.format_table(table, path))
+            .collect::<Res<Vec<_>>>()?;
+
+        if let Some(key) = &self.sort_array_of_tables_by {
+            tables.sort_by(|x, y| match (identity_value(x, key), identity_value(y, key)) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+        }
+
+        let mut formated_array = ArrayOfTables::new();
+        for table in tables {
+            formated_array.push(table);
+        }
+
+        Ok(formated_array)
+    }
+
     /// Format inline tables `{ key = value, key = value }`.
     /// TOML doesn't seem to support inline comments, so we just override entries decors
     /// to respect proper spaces.
-    pub fn format_inline_table(&self, table: &InlineTable, last: bool) -> Res<InlineTable> {
+    pub fn format_inline_table(
+        &self,
+        table: &InlineTable,
+        last: bool,
+        depth: usize,
+        path: &str,
+    ) -> Res<InlineTable> {
+        let inline_keys = self.effective_inline_keys(path);
         let mut formated_table = InlineTable::new();
         if last {
             formated_table.decor_mut().set_suffix(" ");
@@ -281,8 +802,8 @@ impl ProcessedConfig {
         let mut entries = Vec::<Entry<Value>>::new();
 
         let sort = |x: &Entry<Value>, y: &Entry<Value>| {
-            let xord = self.inline_keys.get(&x.key);
-            let yord = self.inline_keys.get(&y.key);
+            let xord = inline_keys.get(&x.key);
+            let yord = inline_keys.get(&y.key);
 
             match (xord, yord) {
                 (Some(_), None) => Ordering::Less,
@@ -312,7 +833,7 @@ impl ProcessedConfig {
 
         let len = entries.len();
         for (i, entry) in entries.into_iter().enumerate() {
-            let new_value = self.format_value(&entry.value, i + 1 == len)?;
+            let new_value = self.format_value(&entry.value, i + 1 == len, depth, path)?;
 
             formated_table.insert(&entry.key, new_value);
             *formated_table.key_decor_mut(&entry.key).unwrap() = entry.decor;
@@ -322,10 +843,12 @@ impl ProcessedConfig {
     }
 
     /// Format a `Value`.
-    pub fn format_value(&self, value: &Value, last: bool) -> Res<Value> {
+    pub fn format_value(&self, value: &Value, last: bool, depth: usize, path: &str) -> Res<Value> {
         Ok(match value {
-            Value::Array(inner) => Value::Array(self.format_array(inner, last)?),
-            Value::InlineTable(inner) => Value::InlineTable(self.format_inline_table(inner, last)?),
+            Value::Array(inner) => Value::Array(self.format_array(inner, last, depth, path)?),
+            Value::InlineTable(inner) => {
+                Value::InlineTable(self.format_inline_table(inner, last, depth, path)?)
+            }
             v => {
                 let mut v = v.clone();
 
@@ -376,15 +899,95 @@ impl ProcessedConfig {
         })
     }
 
+    /// Flip `ord` when `arrays_descending` is set.
+    fn ordered(&self, ord: Ordering) -> Ordering {
+        if self.arrays_descending {
+            ord.reverse()
+        } else {
+            ord
+        }
+    }
+
+    /// Important keys for the non-inline table at `path`, layering the
+    /// `cargo` profile's canonical ordering (if active) over `keys`.
+    fn effective_keys(&self, path: &str) -> BTreeMap<String, usize> {
+        if self.profile == "cargo" {
+            if let Some(order) = cargo_profile_keys(path) {
+                let mut keys: BTreeMap<String, usize> = order
+                    .iter()
+                    .enumerate()
+                    .map(|(i, key)| (key.to_string(), i))
+                    .collect();
+                // Configured `keys` the profile does not name follow its ordering.
+                for (key, i) in &self.keys {
+                    keys.entry(key.clone()).or_insert(order.len() + i);
+                }
+                return keys;
+            }
+        }
+
+        self.keys.clone()
+    }
+
+    /// Important keys for the inline tables found in the table at `path`,
+    /// layering the `cargo` profile's canonical ordering (if active) over
+    /// `inline_keys`.
+    fn effective_inline_keys(&self, path: &str) -> BTreeMap<String, usize> {
+        if self.profile == "cargo" {
+            if let Some(order) = cargo_profile_inline_keys(path) {
+                let mut keys: BTreeMap<String, usize> = order
+                    .iter()
+                    .enumerate()
+                    .map(|(i, key)| (key.to_string(), i))
+                    .collect();
+                // Configured `inline_keys` the profile does not name follow its ordering.
+                for (key, i) in &self.inline_keys {
+                    keys.entry(key.clone()).or_insert(order.len() + i);
+                }
+                return keys;
+            }
+        }
+
+        self.inline_keys.clone()
+    }
+
     /// Format an `Array`.
     /// Detect if the array is inline or multi-line, and format it accordingly.
     /// Support comments in multi-line arrays.
-    /// With config `sort_string_arrays` the array String entries will be sorted, otherwise will be kept
-    /// as is.
-    fn format_array(&self, array: &Array, last: bool) -> Res<Array> {
+    /// With config `sort_arrays` homogeneous arrays of strings, integers, floats
+    /// or booleans are sorted (descending if `arrays_descending` is set); for
+    /// mixed-type arrays strings are ordered first, then other values in
+    /// original order.
+    fn format_array(&self, array: &Array, last: bool, depth: usize, path: &str) -> Res<Array> {
         let mut values: Vec<_> = array.iter().cloned().collect();
 
-        if self.sort_string_arrays {
+        if self.sort_arrays {
+            // Non-string values are only compared in homogeneous arrays, so a
+            // mixed-type array keeps them in their original order.
+            let homogeneous = values
+                .windows(2)
+                .all(|w| std::mem::discriminant(&w[0]) == std::mem::discriminant(&w[1]));
+            values.sort_by(|x, y| match (x, y) {
+                (Value::String(x), Value::String(y)) => {
+                    self.ordered(x.value().cmp(y.value()))
+                }
+                (Value::Integer(x), Value::Integer(y)) if homogeneous => {
+                    self.ordered(x.value().cmp(y.value()))
+                }
+                (Value::Float(x), Value::Float(y)) if homogeneous => self.ordered(
+                    x.value()
+                        .partial_cmp(y.value())
+                        .unwrap_or(Ordering::Equal),
+                ),
+                (Value::Boolean(x), Value::Boolean(y)) if homogeneous => {
+                    self.ordered(x.value().cmp(y.value()))
+                }
+                (Value::String(_), _) => Ordering::Less,
+                (_, Value::String(_)) => Ordering::Greater,
+                (_, _) => Ordering::Equal,
+            });
+        } else if self.sort_string_arrays {
+            // Deprecated: string-only sort, exactly as before `sort_arrays` existed.
             values.sort_by(|x, y| match (x, y) {
                 (Value::String(x), Value::String(y)) => x.value().cmp(y.value()),
                 (Value::String(_), _) => Ordering::Less,
@@ -401,7 +1004,18 @@ impl ProcessedConfig {
 
         // Multiline array
         if array.trailing().starts_with("\n") {
-            new_array.set_trailing(array.trailing());
+            let element_indent = format!("\n{}", self.indent_unit.repeat(depth + 1));
+            let closing_indent = format!("\n{}", self.indent_unit.repeat(depth));
+
+            let trailing = array
+                .trailing()
+                .trim_matches(&[' ', '\t', '\n'][..]);
+            let trailing = if !trailing.is_empty() {
+                format!("{}{}{}", element_indent, trailing, closing_indent)
+            } else {
+                closing_indent.clone()
+            };
+            new_array.set_trailing(&trailing);
             new_array.set_trailing_comma(true);
 
             for value in new_array.iter_mut() {
@@ -412,9 +1026,9 @@ impl ProcessedConfig {
                     .trim_matches(&[' ', '\t', '\n'][..]);
 
                 let prefix = if !prefix.is_empty() {
-                    format!("\n\t{}\n\t", prefix)
+                    format!("{}{}{}", element_indent, prefix, element_indent)
                 } else {
-                    "\n\t".to_string()
+                    element_indent.clone()
                 };
 
                 let suffix = value
@@ -423,7 +1037,7 @@ impl ProcessedConfig {
                     .unwrap_or("")
                     .trim_matches(&[' ', '\t', '\n'][..]);
 
-                let formatted_value = self.format_value(&value, false)?;
+                let formatted_value = self.format_value(&value, false, depth + 1, path)?;
                 *value = formatted_value.decorated(&prefix, suffix);
             }
         }
@@ -434,7 +1048,7 @@ impl ProcessedConfig {
 
             let len = new_array.len();
             for (i, value) in new_array.iter_mut().enumerate() {
-                *value = self.format_value(&value, i + 1 == len)?;
+                *value = self.format_value(&value, i + 1 == len, depth + 1, path)?;
             }
         }
 
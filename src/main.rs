@@ -1,21 +1,35 @@
 use colored::*;
+use std::path::Path;
 use structopt::StructOpt;
 use toml_sort::{Config, Opt, ProcessedConfig, Res};
 
 fn main() -> Res<()> {
     let opt = Opt::from_args();
 
-    let config = Config::read_from_file().unwrap_or_else(|| {
-        println!(
-            "{}",
-            "No 'toml-sort.toml' in this directory and its parents, using default config.\n"
-                .yellow()
-        );
+    let use_stdin = opt.stdin || (opt.files.len() == 1 && opt.files[0] == Path::new("-"));
+
+    let mut config = Config::read_from_file().unwrap_or_else(|| {
+        let notice = "No 'toml-sort.toml' in this directory and its parents, using default config.\n"
+            .yellow();
+        // In stdin mode stdout carries the formatted document itself.
+        if use_stdin {
+            eprintln!("{}", notice);
+        } else {
+            println!("{}", notice);
+        }
         Config::default()
     });
 
+    if let Some(profile) = &opt.profile {
+        config.profile = profile.clone();
+    }
+
     let config: ProcessedConfig = config.into();
 
+    if use_stdin {
+        return config.process_stdin(opt.check, opt.diff);
+    }
+
     if opt.files.is_empty() {
         let _ = Opt::clap().print_help();
         println!();
@@ -23,7 +37,7 @@ fn main() -> Res<()> {
     }
 
     for file in opt.files {
-        config.process_file(file, opt.check)?;
+        config.process_file(file, opt.check, opt.diff)?;
     }
 
     Ok(())